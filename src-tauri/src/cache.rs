@@ -0,0 +1,52 @@
+use crate::file_ops::FileInfo;
+use crate::git_ops::CommitInfo;
+use moka::sync::Cache;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Entries live for this long before a read re-walks the repository, keeping
+/// rapid UI refreshes cheap while still reflecting changes within a few seconds.
+const TTL_SECS: u64 = 10;
+const MAX_ENTRIES: u64 = 256;
+const MAX_COMMITS: u64 = 1024;
+
+/// Shared, managed read-through caches for the git and filesystem read paths.
+/// Values are wrapped in `Arc` so cache hits clone a pointer, not the payload.
+pub struct CacheState {
+    /// `git_log` results keyed by `(repo_path, max_count)`.
+    pub log: Cache<(String, usize), Arc<Vec<CommitInfo>>>,
+    /// `list_directory` results keyed by directory path.
+    pub dir: Cache<String, Arc<Vec<FileInfo>>>,
+    /// Individual commit metadata keyed by its `Oid` string.
+    pub commit: Cache<String, Arc<CommitInfo>>,
+}
+
+impl Default for CacheState {
+    fn default() -> Self {
+        let ttl = Duration::from_secs(TTL_SECS);
+        CacheState {
+            log: Cache::builder().time_to_live(ttl).max_capacity(MAX_ENTRIES).build(),
+            dir: Cache::builder().time_to_live(ttl).max_capacity(MAX_ENTRIES).build(),
+            commit: Cache::builder().time_to_live(ttl).max_capacity(MAX_COMMITS).build(),
+        }
+    }
+}
+
+impl CacheState {
+    /// Drop every cached `git_log` result for a repository after its history changes.
+    pub fn invalidate_repo(&self, repo_path: &str) {
+        let repo_path = repo_path.to_string();
+        let _ = self.log.invalidate_entries_if(move |(path, _), _| path == &repo_path);
+    }
+
+    /// Drop the cached listing for a directory after its contents change.
+    pub fn invalidate_dir(&self, path: &str) {
+        self.dir.invalidate(&path.to_string());
+    }
+
+    /// Drop every cached directory listing after a working-tree-wide change such
+    /// as a branch switch or fast-forward pull, where any path may now differ.
+    pub fn invalidate_all_dirs(&self) {
+        self.dir.invalidate_all();
+    }
+}
@@ -1,9 +1,19 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
+use std::sync::Arc;
 use walkdir::WalkDir;
 
-#[derive(Serialize, Deserialize, Debug)]
+use crate::cache::CacheState;
+
+/// Invalidate the cached listing of a path's parent directory after a mutation.
+fn invalidate_parent(cache: &CacheState, path: &str) {
+    if let Some(parent) = Path::new(path).parent() {
+        cache.invalidate_dir(&parent.to_string_lossy());
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct FileInfo {
     pub name: String,
     pub path: String,
@@ -17,17 +27,24 @@ pub fn read_file_content(path: String) -> Result<String, String> {
 }
 
 #[tauri::command]
-pub fn write_file_content(path: String, content: String) -> Result<(), String> {
+pub fn write_file_content(cache: tauri::State<'_, CacheState>, path: String, content: String) -> Result<(), String> {
     // Ensure parent directory exists
     if let Some(parent) = Path::new(&path).parent() {
         fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
     }
 
-    fs::write(&path, content).map_err(|e| format!("Failed to write file: {}", e))
+    fs::write(&path, content).map_err(|e| format!("Failed to write file: {}", e))?;
+
+    invalidate_parent(&cache, &path);
+    Ok(())
 }
 
 #[tauri::command]
-pub fn list_directory(path: String) -> Result<Vec<FileInfo>, String> {
+pub fn list_directory(cache: tauri::State<'_, CacheState>, path: String) -> Result<Vec<FileInfo>, String> {
+    if let Some(cached) = cache.dir.get(&path) {
+        return Ok((*cached).clone());
+    }
+
     let mut files = Vec::new();
 
     for entry in WalkDir::new(&path)
@@ -51,23 +68,33 @@ pub fn list_directory(path: String) -> Result<Vec<FileInfo>, String> {
         });
     }
 
+    cache.dir.insert(path, Arc::new(files.clone()));
     Ok(files)
 }
 
 #[tauri::command]
-pub fn create_directory(path: String) -> Result<(), String> {
-    fs::create_dir_all(&path).map_err(|e| format!("Failed to create directory: {}", e))
+pub fn create_directory(cache: tauri::State<'_, CacheState>, path: String) -> Result<(), String> {
+    fs::create_dir_all(&path).map_err(|e| format!("Failed to create directory: {}", e))?;
+
+    // Both the new directory's own (empty) listing and its parent are stale.
+    cache.invalidate_dir(&path);
+    invalidate_parent(&cache, &path);
+    Ok(())
 }
 
 #[tauri::command]
-pub fn delete_file(path: String) -> Result<(), String> {
+pub fn delete_file(cache: tauri::State<'_, CacheState>, path: String) -> Result<(), String> {
     let path_obj = Path::new(&path);
 
     if path_obj.is_dir() {
-        fs::remove_dir_all(&path).map_err(|e| format!("Failed to delete directory: {}", e))
+        fs::remove_dir_all(&path).map_err(|e| format!("Failed to delete directory: {}", e))?;
     } else {
-        fs::remove_file(&path).map_err(|e| format!("Failed to delete file: {}", e))
+        fs::remove_file(&path).map_err(|e| format!("Failed to delete file: {}", e))?;
     }
+
+    cache.invalidate_dir(&path);
+    invalidate_parent(&cache, &path);
+    Ok(())
 }
 
 #[tauri::command]
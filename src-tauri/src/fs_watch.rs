@@ -0,0 +1,118 @@
+use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::Manager;
+
+/// How long to coalesce a burst of filesystem events before flushing them to
+/// the frontend. External writers (the Python backend, git restores) tend to
+/// touch several files at once, so a short debounce avoids a flood of events.
+const DEBOUNCE_MS: u64 = 250;
+
+#[derive(Clone, Serialize)]
+pub struct FileChangeEvent {
+    pub path: String,
+    pub kind: String,
+}
+
+struct WatcherHandle {
+    stop: Arc<AtomicBool>,
+    // The watcher is kept alive here; dropping it tears down the OS watch.
+    _watcher: RecommendedWatcher,
+}
+
+/// Managed Tauri state holding the single active project watcher, if any.
+#[derive(Default)]
+pub struct WatcherState {
+    inner: Mutex<Option<WatcherHandle>>,
+}
+
+/// Translate a notify `EventKind` into our coarse Created/Modified/Removed label,
+/// returning `None` for kinds the editor does not care about.
+fn classify(kind: &EventKind) -> Option<&'static str> {
+    match kind {
+        EventKind::Create(_) => Some("Created"),
+        EventKind::Modify(_) => Some("Modified"),
+        EventKind::Remove(_) => Some("Removed"),
+        _ => None,
+    }
+}
+
+/// True for paths inside a `.git/` directory, which churn constantly and are
+/// never part of the editable project surface.
+fn is_git_internal(path: &std::path::Path) -> bool {
+    path.components().any(|c| c.as_os_str() == ".git")
+}
+
+#[tauri::command]
+pub fn start_watching(app: tauri::AppHandle, state: tauri::State<'_, WatcherState>, path: String) -> Result<(), String> {
+    // Replace any watcher already running so only one project is watched at a time.
+    stop_watching(state.clone());
+
+    let (tx, rx) = channel();
+
+    let mut watcher = RecommendedWatcher::new(
+        move |res| {
+            let _ = tx.send(res);
+        },
+        Config::default(),
+    ).map_err(|e| format!("Failed to create watcher: {}", e))?;
+
+    watcher.watch(std::path::Path::new(&path), RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch path: {}", e))?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread_stop = stop.clone();
+
+    std::thread::spawn(move || {
+        // Coalesce a burst of events, keeping only the latest kind per path, and
+        // flush once `DEBOUNCE_MS` passes with no further events.
+        let mut pending: HashMap<PathBuf, &'static str> = HashMap::new();
+        while !thread_stop.load(Ordering::Relaxed) {
+            match rx.recv_timeout(Duration::from_millis(DEBOUNCE_MS)) {
+                Ok(Ok(event)) => accumulate(event, &mut pending),
+                Ok(Err(_)) => {}
+                Err(RecvTimeoutError::Timeout) => flush(&app, &mut pending),
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    });
+
+    *state.inner.lock().unwrap() = Some(WatcherHandle { stop, _watcher: watcher });
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_watching(state: tauri::State<'_, WatcherState>) {
+    if let Some(handle) = state.inner.lock().unwrap().take() {
+        handle.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Record an event's paths into the pending set, letting the most recent kind
+/// for a given path win so several raw events collapse to one emission.
+fn accumulate(event: Event, pending: &mut HashMap<PathBuf, &'static str>) {
+    let Some(kind) = classify(&event.kind) else { return };
+
+    for path in event.paths {
+        if is_git_internal(&path) {
+            continue;
+        }
+        pending.insert(path, kind);
+    }
+}
+
+/// Emit one `file-changed` event per deduped path, then clear the pending set.
+fn flush(app: &tauri::AppHandle, pending: &mut HashMap<PathBuf, &'static str>) {
+    for (path, kind) in pending.drain() {
+        let _ = app.emit_all("file-changed", FileChangeEvent {
+            path: path.to_string_lossy().into_owned(),
+            kind: kind.to_string(),
+        });
+    }
+}
@@ -1,8 +1,12 @@
-use git2::{Repository, Signature, IndexAddOption, DiffOptions, Oid};
+use git2::{Repository, Signature, IndexAddOption, DiffOptions, DiffFormat, DiffLineType, BranchType, Cred, FetchOptions, PushOptions, RemoteCallbacks, build::CheckoutBuilder, Oid};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
+use std::sync::Arc;
+use tauri::Manager;
 
-#[derive(Serialize, Deserialize, Debug)]
+use crate::cache::CacheState;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct CommitInfo {
     pub id: String,
     pub message: String,
@@ -10,6 +14,100 @@ pub struct CommitInfo {
     pub timestamp: i64,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DiffLine {
+    /// Origin marker: '+' addition, '-' deletion, ' ' context, or one of the
+    /// end-of-file newline markers '=', '>' and '<'.
+    pub origin: char,
+    pub old_lineno: Option<u32>,
+    pub new_lineno: Option<u32>,
+    pub content: String,
+    /// Whitespace-split tokens, populated only when word-level diffing is requested
+    /// and the line was added or removed. Lets the UI highlight changed words.
+    pub words: Option<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BranchInfo {
+    pub name: String,
+    pub is_head: bool,
+    pub tip: Option<String>,
+}
+
+/// Credentials supplied by the caller for a remote operation. The credentials
+/// callback tries an SSH agent first, then this key path, then the HTTPS token.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct RemoteAuth {
+    pub ssh_key_path: Option<String>,
+    pub username: Option<String>,
+    pub token: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+struct TransferProgress {
+    received_objects: usize,
+    total_objects: usize,
+    received_bytes: usize,
+}
+
+/// Build `RemoteCallbacks` wired for authentication and transfer progress.
+/// Credentials are tried in order: SSH agent, explicit SSH key, HTTPS token.
+fn build_callbacks<'a>(app: tauri::AppHandle, auth: &'a RemoteAuth) -> RemoteCallbacks<'a> {
+    let mut callbacks = RemoteCallbacks::new();
+
+    // git2 re-invokes this callback when the server rejects an offered
+    // credential, so each method is tried at most once; otherwise a rejected
+    // agent key would be resubmitted forever and hang the operation.
+    let mut tried_agent = false;
+    let mut tried_ssh_key = false;
+    let mut tried_userpass = false;
+    callbacks.credentials(move |_url, username_from_url, allowed_types| {
+        let user = username_from_url.unwrap_or("git");
+
+        if allowed_types.is_ssh_key() {
+            if !tried_agent {
+                tried_agent = true;
+                if let Ok(cred) = Cred::ssh_key_from_agent(user) {
+                    return Ok(cred);
+                }
+            }
+            if !tried_ssh_key {
+                tried_ssh_key = true;
+                if let Some(key) = &auth.ssh_key_path {
+                    return Cred::ssh_key(user, None, Path::new(key), None);
+                }
+            }
+        }
+
+        if allowed_types.is_user_pass_plaintext() && !tried_userpass {
+            tried_userpass = true;
+            if let Some(token) = &auth.token {
+                let name = auth.username.as_deref().unwrap_or(user);
+                return Cred::userpass_plaintext(name, token);
+            }
+        }
+
+        Err(git2::Error::from_str("no suitable credentials available"))
+    });
+
+    callbacks.transfer_progress(move |stats| {
+        let _ = app.emit_all("git-transfer", TransferProgress {
+            received_objects: stats.received_objects(),
+            total_objects: stats.total_objects(),
+            received_bytes: stats.received_bytes(),
+        });
+        true
+    });
+
+    callbacks
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DiffHunk {
+    pub header: String,
+    pub lines: Vec<DiffLine>,
+}
+
 #[tauri::command]
 pub fn init_git_repo(path: String) -> Result<(), String> {
     Repository::init(&path).map_err(|e| format!("Failed to initialize git repository: {}", e))?;
@@ -17,7 +115,7 @@ pub fn init_git_repo(path: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub fn git_commit(repo_path: String, message: String, author_name: String, author_email: String) -> Result<String, String> {
+pub fn git_commit(cache: tauri::State<'_, CacheState>, repo_path: String, message: String, author_name: String, author_email: String) -> Result<String, String> {
     let repo = Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
 
     let signature = Signature::now(&author_name, &author_email)
@@ -53,6 +151,9 @@ pub fn git_commit(repo_path: String, message: String, author_name: String, autho
         &parents,
     ).map_err(|e| format!("Failed to create commit: {}", e))?;
 
+    // History changed: stale any cached log for this repository.
+    cache.invalidate_repo(&repo_path);
+
     Ok(commit_id.to_string())
 }
 
@@ -73,7 +174,12 @@ pub fn git_status(repo_path: String) -> Result<Vec<String>, String> {
 }
 
 #[tauri::command]
-pub fn git_log(repo_path: String, max_count: usize) -> Result<Vec<CommitInfo>, String> {
+pub fn git_log(cache: tauri::State<'_, CacheState>, repo_path: String, max_count: usize) -> Result<Vec<CommitInfo>, String> {
+    let key = (repo_path.clone(), max_count);
+    if let Some(cached) = cache.log.get(&key) {
+        return Ok((*cached).clone());
+    }
+
     let repo = Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
 
     let mut revwalk = repo.revwalk().map_err(|e| format!("Failed to create revwalk: {}", e))?;
@@ -86,19 +192,33 @@ pub fn git_log(repo_path: String, max_count: usize) -> Result<Vec<CommitInfo>, S
         }
 
         let oid = oid.map_err(|e| format!("Failed to get oid: {}", e))?;
-        let commit = repo.find_commit(oid).map_err(|e| format!("Failed to find commit: {}", e))?;
-
-        commits.push(CommitInfo {
-            id: commit.id().to_string(),
-            message: commit.message().unwrap_or("").to_string(),
-            author: commit.author().name().unwrap_or("").to_string(),
-            timestamp: commit.time().seconds(),
-        });
+        commits.push((*load_commit(&cache, &repo, oid)?).clone());
     }
 
+    cache.log.insert(key, Arc::new(commits.clone()));
     Ok(commits)
 }
 
+/// Read-through lookup of a single commit's metadata, cached by `Oid`. Commits
+/// are immutable, so entries only ever expire by TTL, never by invalidation.
+fn load_commit(cache: &CacheState, repo: &Repository, oid: Oid) -> Result<Arc<CommitInfo>, String> {
+    let key = oid.to_string();
+    if let Some(cached) = cache.commit.get(&key) {
+        return Ok(cached);
+    }
+
+    let commit = repo.find_commit(oid).map_err(|e| format!("Failed to find commit: {}", e))?;
+    let info = Arc::new(CommitInfo {
+        id: commit.id().to_string(),
+        message: commit.message().unwrap_or("").to_string(),
+        author: commit.author().name().unwrap_or("").to_string(),
+        timestamp: commit.time().seconds(),
+    });
+
+    cache.commit.insert(key, info.clone());
+    Ok(info)
+}
+
 #[tauri::command]
 pub fn git_diff(repo_path: String, file_path: Option<String>) -> Result<String, String> {
     let repo = Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
@@ -122,7 +242,356 @@ pub fn git_diff(repo_path: String, file_path: Option<String>) -> Result<String,
 }
 
 #[tauri::command]
-pub fn restore_file_version(repo_path: String, file_path: String, commit_id: String) -> Result<(), String> {
+pub fn git_diff_hunks(repo_path: String, file_path: Option<String>, word_level: bool) -> Result<Vec<DiffHunk>, String> {
+    let repo = Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+
+    let head = repo.head().map_err(|e| format!("Failed to get HEAD: {}", e))?;
+    let tree = head.peel_to_tree().map_err(|e| format!("Failed to get tree: {}", e))?;
+
+    let mut opts = DiffOptions::new();
+    if let Some(path) = file_path {
+        opts.pathspec(path);
+    }
+
+    let diff = repo.diff_tree_to_workdir(Some(&tree), Some(&mut opts))
+        .map_err(|e| format!("Failed to create diff: {}", e))?;
+
+    let mut hunks: Vec<DiffHunk> = Vec::new();
+
+    diff.print(DiffFormat::Patch, |_delta, hunk, line| {
+        let content = String::from_utf8_lossy(line.content()).into_owned();
+
+        match line.origin_value() {
+            // Hunk headers open a new hunk; body lines attach to the current one.
+            DiffLineType::HunkHeader => {
+                let header = match hunk {
+                    Some(h) => String::from_utf8_lossy(h.header()).into_owned(),
+                    None => content,
+                };
+                hunks.push(DiffHunk { header, lines: Vec::new() });
+            }
+            // File headers and binary markers are not part of a hunk body.
+            DiffLineType::FileHeader | DiffLineType::Binary => {}
+            origin => {
+                let is_change = matches!(origin, DiffLineType::Addition | DiffLineType::Deletion);
+                let words = if word_level && is_change {
+                    Some(content.split_whitespace().map(|w| w.to_string()).collect())
+                } else {
+                    None
+                };
+
+                let diff_line = DiffLine {
+                    origin: line.origin(),
+                    old_lineno: line.old_lineno(),
+                    new_lineno: line.new_lineno(),
+                    content,
+                    words,
+                };
+
+                if let Some(current) = hunks.last_mut() {
+                    current.lines.push(diff_line);
+                } else {
+                    // Defensive: lines before any hunk header get their own hunk.
+                    hunks.push(DiffHunk { header: String::new(), lines: vec![diff_line] });
+                }
+            }
+        }
+
+        true
+    }).map_err(|e| format!("Failed to walk diff: {}", e))?;
+
+    Ok(hunks)
+}
+
+#[tauri::command]
+pub fn git_list_branches(repo_path: String) -> Result<Vec<BranchInfo>, String> {
+    let repo = Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+
+    let branches = repo.branches(Some(BranchType::Local))
+        .map_err(|e| format!("Failed to list branches: {}", e))?;
+
+    let mut result = Vec::new();
+    for branch in branches {
+        let (branch, _) = branch.map_err(|e| format!("Failed to read branch: {}", e))?;
+        let name = branch.name().map_err(|e| format!("Failed to read branch name: {}", e))?
+            .unwrap_or("")
+            .to_string();
+        let tip = branch.get().target().map(|oid| oid.to_string());
+
+        result.push(BranchInfo {
+            name,
+            is_head: branch.is_head(),
+            tip,
+        });
+    }
+
+    Ok(result)
+}
+
+#[tauri::command]
+pub fn git_create_branch(repo_path: String, name: String, from_commit: Option<String>) -> Result<(), String> {
+    let repo = Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+
+    let target = match from_commit {
+        Some(id) => {
+            let oid = Oid::from_str(&id).map_err(|e| format!("Invalid commit ID: {}", e))?;
+            repo.find_commit(oid).map_err(|e| format!("Failed to find commit: {}", e))?
+        }
+        None => {
+            let head = repo.head().map_err(|e| format!("Failed to get HEAD: {}", e))?;
+            head.peel_to_commit().map_err(|e| format!("Failed to get HEAD commit: {}", e))?
+        }
+    };
+
+    repo.branch(&name, &target, false)
+        .map_err(|e| format!("Failed to create branch: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn git_checkout_branch(cache: tauri::State<'_, CacheState>, repo_path: String, name: String) -> Result<(), String> {
+    let repo = Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+
+    let refname = format!("refs/heads/{}", name);
+    let object = repo.revparse_single(&refname)
+        .map_err(|e| format!("Failed to resolve branch: {}", e))?;
+
+    // `.safe()` aborts the checkout if uncommitted edits would be overwritten,
+    // so a writer never silently loses work when switching drafts.
+    let mut checkout = CheckoutBuilder::new();
+    checkout.safe();
+
+    repo.checkout_tree(&object, Some(&mut checkout))
+        .map_err(|e| format!("Failed to checkout branch: {}", e))?;
+
+    repo.set_head(&refname)
+        .map_err(|e| format!("Failed to set HEAD: {}", e))?;
+
+    // The working tree and history now reflect a different branch, so stale
+    // log and directory listings must not be served for the rest of the TTL.
+    cache.invalidate_repo(&repo_path);
+    cache.invalidate_all_dirs();
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn git_delete_branch(repo_path: String, name: String) -> Result<(), String> {
+    let repo = Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+
+    let mut branch = repo.find_branch(&name, BranchType::Local)
+        .map_err(|e| format!("Failed to find branch: {}", e))?;
+
+    branch.delete().map_err(|e| format!("Failed to delete branch: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn git_clone(app: tauri::AppHandle, url: String, into_path: String, auth: Option<RemoteAuth>) -> Result<(), String> {
+    let auth = auth.unwrap_or_default();
+    let callbacks = build_callbacks(app, &auth);
+
+    let mut fetch_opts = FetchOptions::new();
+    fetch_opts.remote_callbacks(callbacks);
+
+    let mut builder = git2::build::RepoBuilder::new();
+    builder.fetch_options(fetch_opts);
+
+    builder.clone(&url, Path::new(&into_path))
+        .map_err(|e| format!("Failed to clone: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn git_add_remote(repo_path: String, name: String, url: String) -> Result<(), String> {
+    let repo = Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+
+    repo.remote(&name, &url).map_err(|e| format!("Failed to add remote: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn git_fetch(app: tauri::AppHandle, repo_path: String, remote: String, auth: Option<RemoteAuth>) -> Result<(), String> {
+    let repo = Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let auth = auth.unwrap_or_default();
+
+    let mut remote = repo.find_remote(&remote)
+        .map_err(|e| format!("Failed to find remote: {}", e))?;
+
+    let callbacks = build_callbacks(app, &auth);
+    let mut fetch_opts = FetchOptions::new();
+    fetch_opts.remote_callbacks(callbacks);
+
+    let refspecs: Vec<String> = Vec::new();
+    remote.fetch(&refspecs, Some(&mut fetch_opts), None)
+        .map_err(|e| format!("Failed to fetch: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn git_push(app: tauri::AppHandle, repo_path: String, remote: String, auth: Option<RemoteAuth>) -> Result<(), String> {
+    let repo = Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let auth = auth.unwrap_or_default();
+
+    let head = repo.head().map_err(|e| format!("Failed to get HEAD: {}", e))?;
+    let branch = head.shorthand()
+        .ok_or_else(|| "HEAD is not on a branch".to_string())?
+        .to_string();
+
+    let mut remote = repo.find_remote(&remote)
+        .map_err(|e| format!("Failed to find remote: {}", e))?;
+
+    let callbacks = build_callbacks(app, &auth);
+    let mut push_opts = PushOptions::new();
+    push_opts.remote_callbacks(callbacks);
+
+    let refspec = format!("refs/heads/{0}:refs/heads/{0}", branch);
+    remote.push(&[refspec], Some(&mut push_opts))
+        .map_err(|e| format!("Failed to push: {}", e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn git_pull(app: tauri::AppHandle, cache: tauri::State<'_, CacheState>, repo_path: String, remote: String, auth: Option<RemoteAuth>) -> Result<(), String> {
+    let repo = Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let auth = auth.unwrap_or_default();
+
+    let head = repo.head().map_err(|e| format!("Failed to get HEAD: {}", e))?;
+    let branch = head.shorthand()
+        .ok_or_else(|| "HEAD is not on a branch".to_string())?
+        .to_string();
+
+    // Fetch the remote tracking branch first.
+    {
+        let mut remote = repo.find_remote(&remote)
+            .map_err(|e| format!("Failed to find remote: {}", e))?;
+        let callbacks = build_callbacks(app, &auth);
+        let mut fetch_opts = FetchOptions::new();
+        fetch_opts.remote_callbacks(callbacks);
+        remote.fetch(&[&branch], Some(&mut fetch_opts), None)
+            .map_err(|e| format!("Failed to fetch: {}", e))?;
+    }
+
+    // Analyze whether the fetched head can be fast-forwarded onto ours.
+    let fetch_head = repo.find_reference("FETCH_HEAD")
+        .map_err(|e| format!("Failed to read FETCH_HEAD: {}", e))?;
+    let fetch_commit = repo.reference_to_annotated_commit(&fetch_head)
+        .map_err(|e| format!("Failed to resolve FETCH_HEAD: {}", e))?;
+
+    let (analysis, _) = repo.merge_analysis(&[&fetch_commit])
+        .map_err(|e| format!("Failed to analyze merge: {}", e))?;
+
+    if analysis.is_up_to_date() {
+        return Ok(());
+    }
+
+    if !analysis.is_fast_forward() {
+        return Err("Branches have diverged; a manual merge is required".to_string());
+    }
+
+    // Fast-forward the local branch reference to the fetched commit.
+    let refname = format!("refs/heads/{}", branch);
+    let mut reference = repo.find_reference(&refname)
+        .map_err(|e| format!("Failed to find branch reference: {}", e))?;
+    reference.set_target(fetch_commit.id(), "pull: fast-forward")
+        .map_err(|e| format!("Failed to fast-forward: {}", e))?;
+
+    repo.set_head(&refname).map_err(|e| format!("Failed to set HEAD: {}", e))?;
+    repo.checkout_head(Some(CheckoutBuilder::new().force()))
+        .map_err(|e| format!("Failed to checkout: {}", e))?;
+
+    // The fast-forward advanced history and rewrote the working tree, so drop
+    // cached log and directory listings for this repository.
+    cache.invalidate_repo(&repo_path);
+    cache.invalidate_all_dirs();
+
+    Ok(())
+}
+
+/// Produce an mbox-formatted email patch for `commit` against its first parent
+/// (or against the empty tree for a root commit) and return the bytes.
+fn format_commit_patch(repo: &Repository, commit: &git2::Commit, idx: usize, total: usize) -> Result<Vec<u8>, String> {
+    let tree = commit.tree().map_err(|e| format!("Failed to get tree: {}", e))?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree().map_err(|e| format!("Failed to get parent tree: {}", e))?),
+        Err(_) => None,
+    };
+
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+        .map_err(|e| format!("Failed to create diff: {}", e))?;
+
+    let summary = commit.summary().unwrap_or("");
+    let body = commit.body();
+
+    let mut opts = git2::EmailCreateOptions::new();
+    let email = git2::Email::from_diff(
+        &diff,
+        idx,
+        total,
+        &commit.id(),
+        summary,
+        body,
+        &commit.author(),
+        &mut opts,
+    ).map_err(|e| format!("Failed to build email patch: {}", e))?;
+
+    Ok(email.as_slice().to_vec())
+}
+
+#[tauri::command]
+pub fn git_export_patch(repo_path: String, commit_id: String, out_path: String) -> Result<String, String> {
+    let repo = Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+
+    let oid = Oid::from_str(&commit_id).map_err(|e| format!("Invalid commit ID: {}", e))?;
+    let commit = repo.find_commit(oid).map_err(|e| format!("Failed to find commit: {}", e))?;
+
+    let bytes = format_commit_patch(&repo, &commit, 1, 1)?;
+
+    std::fs::write(&out_path, bytes).map_err(|e| format!("Failed to write patch: {}", e))?;
+
+    Ok(out_path)
+}
+
+#[tauri::command]
+pub fn git_export_patch_range(repo_path: String, from_commit: String, to_commit: String, out_path: String) -> Result<String, String> {
+    let repo = Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+
+    // Walk the commits reachable from `to_commit` but not from `from_commit`,
+    // oldest first, so the exported patches apply in order.
+    let mut revwalk = repo.revwalk().map_err(|e| format!("Failed to create revwalk: {}", e))?;
+    revwalk.push_range(&format!("{}..{}", from_commit, to_commit))
+        .map_err(|e| format!("Invalid commit range: {}", e))?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)
+        .map_err(|e| format!("Failed to sort revwalk: {}", e))?;
+
+    let oids: Vec<Oid> = revwalk
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("Failed to walk range: {}", e))?;
+
+    if oids.is_empty() {
+        return Err("No commits in the given range".to_string());
+    }
+
+    let total = oids.len();
+    let mut buf = Vec::new();
+    for (i, oid) in oids.iter().enumerate() {
+        let commit = repo.find_commit(*oid).map_err(|e| format!("Failed to find commit: {}", e))?;
+        buf.extend(format_commit_patch(&repo, &commit, i + 1, total)?);
+    }
+
+    std::fs::write(&out_path, buf).map_err(|e| format!("Failed to write patch: {}", e))?;
+
+    Ok(out_path)
+}
+
+#[tauri::command]
+pub fn restore_file_version(cache: tauri::State<'_, CacheState>, repo_path: String, file_path: String, commit_id: String) -> Result<(), String> {
     let repo = Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
 
     let oid = Oid::from_str(&commit_id).map_err(|e| format!("Invalid commit ID: {}", e))?;
@@ -138,8 +607,13 @@ pub fn restore_file_version(repo_path: String, file_path: String, commit_id: Str
     let content = blob.content();
     let full_path = Path::new(&repo_path).join(&file_path);
 
-    std::fs::write(full_path, content)
+    std::fs::write(&full_path, content)
         .map_err(|e| format!("Failed to write file: {}", e))?;
 
+    // The restored file's directory listing (size) is now stale.
+    if let Some(parent) = full_path.parent() {
+        cache.invalidate_dir(&parent.to_string_lossy());
+    }
+
     Ok(())
 }
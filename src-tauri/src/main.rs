@@ -1,12 +1,22 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod cache;
 mod file_ops;
+mod fs_watch;
 mod git_ops;
+mod render;
+
+use cache::CacheState;
+use render::*;
 
 use file_ops::*;
+use fs_watch::*;
 use git_ops::*;
 
+use serde::Serialize;
+use tauri::Manager;
+
 // Only import process types when building for production
 #[cfg(not(debug_assertions))]
 use tauri::api::process::{Command, CommandEvent};
@@ -14,41 +24,63 @@ use tauri::api::process::{Command, CommandEvent};
 const BACKEND_HOST: &str = "127.0.0.1";
 const BACKEND_PORT: u16 = 8000;
 
-#[tauri::command]
-fn check_backend_health() -> Result<bool, String> {
-    // Simple health check - try to connect to the backend
-    match std::net::TcpStream::connect(format!("{}:{}", BACKEND_HOST, BACKEND_PORT)) {
-        Ok(_) => Ok(true),
-        Err(_) => Ok(false),
-    }
+/// Supervisor tuning: how far the restart backoff grows, and how many restarts
+/// are tolerated inside a rolling window before we stop trying (crash-loop guard).
+#[cfg(not(debug_assertions))]
+const BACKOFF_INITIAL_MS: u64 = 500;
+#[cfg(not(debug_assertions))]
+const BACKOFF_MAX_MS: u64 = 30_000;
+#[cfg(not(debug_assertions))]
+const MAX_RESTARTS_PER_WINDOW: usize = 5;
+#[cfg(not(debug_assertions))]
+const RESTART_WINDOW_SECS: u64 = 60;
+
+/// How often the background poller re-checks backend health.
+const HEALTH_POLL_INTERVAL_MS: u64 = 3_000;
+
+/// Lifecycle status broadcast to the frontend on the `backend-status` event so
+/// UI banners can react to transitions instead of polling `check_backend_health`.
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum BackendStatus {
+    Starting,
+    Healthy,
+    Unhealthy,
+    Restarting,
 }
 
-/// Wait for the backend to become healthy with exponential backoff
-fn wait_for_backend_health(max_attempts: u32, initial_delay_ms: u64) -> bool {
-    let mut delay = initial_delay_ms;
+fn emit_backend_status(app: &tauri::AppHandle, status: BackendStatus) {
+    let _ = app.emit_all("backend-status", status);
+}
 
-    for attempt in 1..=max_attempts {
-        println!("Checking backend health (attempt {}/{})", attempt, max_attempts);
+fn backend_reachable() -> bool {
+    std::net::TcpStream::connect(format!("{}:{}", BACKEND_HOST, BACKEND_PORT)).is_ok()
+}
 
-        match std::net::TcpStream::connect(format!("{}:{}", BACKEND_HOST, BACKEND_PORT)) {
-            Ok(_) => {
-                println!("Backend is healthy and ready!");
-                return true;
-            }
-            Err(e) => {
-                if attempt < max_attempts {
-                    println!("Backend not ready yet ({}), retrying in {}ms...", e, delay);
-                    std::thread::sleep(std::time::Duration::from_millis(delay));
-                    // Exponential backoff with max of 5 seconds
-                    delay = std::cmp::min(delay * 2, 5000);
-                } else {
-                    eprintln!("Backend failed to start after {} attempts", max_attempts);
-                }
+/// Poll backend health on a fixed interval and emit `backend-status` only on
+/// transitions. `check_backend_health` does not read the poller's state; it
+/// probes the backend port itself on each call.
+fn start_health_poller(app: tauri::AppHandle) {
+    std::thread::spawn(move || {
+        let mut last_healthy: Option<bool> = None;
+        loop {
+            let healthy = backend_reachable();
+            if last_healthy != Some(healthy) {
+                emit_backend_status(&app, if healthy { BackendStatus::Healthy } else { BackendStatus::Unhealthy });
+                last_healthy = Some(healthy);
             }
+            std::thread::sleep(std::time::Duration::from_millis(HEALTH_POLL_INTERVAL_MS));
         }
-    }
+    });
+}
 
-    false
+#[tauri::command]
+fn check_backend_health() -> Result<bool, String> {
+    // Simple health check - try to connect to the backend
+    match std::net::TcpStream::connect(format!("{}:{}", BACKEND_HOST, BACKEND_PORT)) {
+        Ok(_) => Ok(true),
+        Err(_) => Ok(false),
+    }
 }
 
 #[tauri::command]
@@ -72,66 +104,94 @@ fn get_home_dir() -> Result<String, String> {
     }
 }
 
-fn start_backend_server(_app_handle: tauri::AppHandle) {
-    std::thread::spawn(move || {
-        // In production, use the sidecar binary
-        #[cfg(not(debug_assertions))]
-        {
-            let port_arg = BACKEND_PORT.to_string();
-            println!("Starting backend server on {}:{}", BACKEND_HOST, BACKEND_PORT);
-
-            let (mut rx, _child) = Command::new_sidecar("novel-writer-backend")
-                .expect("failed to create `novel-writer-backend` binary command")
-                .args(&["--host", BACKEND_HOST, "--port", &port_arg])
-                .spawn()
-                .expect("Failed to spawn backend server");
-
-            tauri::async_runtime::spawn(async move {
-                while let Some(event) = rx.recv().await {
-                    match event {
-                        CommandEvent::Stdout(line) => println!("Backend: {}", line),
-                        CommandEvent::Stderr(line) => eprintln!("Backend Error: {}", line),
-                        CommandEvent::Error(err) => eprintln!("Backend Process Error: {}", err),
-                        CommandEvent::Terminated(payload) => {
-                            eprintln!("Backend terminated with code: {:?}", payload.code);
-                            break;
+fn start_backend_server(app_handle: tauri::AppHandle) {
+    // In production, supervise the sidecar: spawn it, watch for termination, and
+    // restart with exponential backoff until the crash-loop guard trips.
+    #[cfg(not(debug_assertions))]
+    {
+        std::thread::spawn(move || {
+            let mut backoff = BACKOFF_INITIAL_MS;
+            // Timestamps of recent restarts, used to detect a crash-loop.
+            let mut restarts: Vec<std::time::Instant> = Vec::new();
+
+            loop {
+                let port_arg = BACKEND_PORT.to_string();
+                println!("Starting backend server on {}:{}", BACKEND_HOST, BACKEND_PORT);
+                emit_backend_status(&app_handle, BackendStatus::Starting);
+
+                let spawned = Command::new_sidecar("novel-writer-backend")
+                    .and_then(|cmd| cmd.args(&["--host", BACKEND_HOST, "--port", &port_arg]).spawn());
+
+                let (mut rx, _child) = match spawned {
+                    Ok(pair) => pair,
+                    Err(err) => {
+                        eprintln!("Failed to spawn backend server: {}", err);
+                        emit_backend_status(&app_handle, BackendStatus::Unhealthy);
+                        break;
+                    }
+                };
+
+                // Block this supervisor thread until the sidecar terminates,
+                // forwarding its output in the meantime.
+                tauri::async_runtime::block_on(async {
+                    while let Some(event) = rx.recv().await {
+                        match event {
+                            CommandEvent::Stdout(line) => println!("Backend: {}", line),
+                            CommandEvent::Stderr(line) => eprintln!("Backend Error: {}", line),
+                            CommandEvent::Error(err) => eprintln!("Backend Process Error: {}", err),
+                            CommandEvent::Terminated(payload) => {
+                                eprintln!("Backend terminated with code: {:?}", payload.code);
+                                break;
+                            }
+                            _ => {}
                         }
-                        _ => {}
                     }
+                });
+
+                // The sidecar exited. Drop restart timestamps outside the rolling
+                // window, then bail out if we are restarting too frequently.
+                let now = std::time::Instant::now();
+                restarts.retain(|t| now.duration_since(*t).as_secs() < RESTART_WINDOW_SECS);
+                if restarts.len() >= MAX_RESTARTS_PER_WINDOW {
+                    eprintln!(
+                        "Backend crashed {} times within {}s; giving up to avoid a crash-loop",
+                        restarts.len() + 1, RESTART_WINDOW_SECS
+                    );
+                    emit_backend_status(&app_handle, BackendStatus::Unhealthy);
+                    break;
                 }
-            });
-        }
+                restarts.push(now);
 
-        // In development, expect the backend to be run manually
-        #[cfg(debug_assertions)]
-        {
-            println!("Development mode: Please start the Python backend manually:");
-            println!("  cd python-backend && uvicorn main:app --reload --port {}", BACKEND_PORT);
-        }
-    });
+                eprintln!("Restarting backend in {}ms...", backoff);
+                emit_backend_status(&app_handle, BackendStatus::Restarting);
+                std::thread::sleep(std::time::Duration::from_millis(backoff));
+                backoff = std::cmp::min(backoff * 2, BACKOFF_MAX_MS);
+            }
+        });
+    }
+
+    // In development, expect the backend to be run manually.
+    #[cfg(debug_assertions)]
+    {
+        let _ = &app_handle;
+        println!("Development mode: Please start the Python backend manually:");
+        println!("  cd python-backend && uvicorn main:app --reload --port {}", BACKEND_PORT);
+    }
 }
 
 fn main() {
     tauri::Builder::default()
+        .manage(WatcherState::default())
+        .manage(CacheState::default())
+        .manage(RenderCache::default())
         .setup(|app| {
-            // Start the backend server
+            // Start (and, in production, supervise) the backend server.
             start_backend_server(app.handle());
 
-            // Wait for the backend to become healthy with exponential backoff
-            // Try up to 10 times, starting with 500ms delay
-            // This allows for up to ~30 seconds total wait time
-            #[cfg(not(debug_assertions))]
-            {
-                if !wait_for_backend_health(10, 500) {
-                    eprintln!("Warning: Backend server may not be ready. The application will continue but some features may not work.");
-                }
-            }
-
-            // In development, just wait a bit for manual backend startup
-            #[cfg(debug_assertions)]
-            {
-                std::thread::sleep(std::time::Duration::from_millis(500));
-            }
+            // Keep backend health fresh in the background and broadcast
+            // `backend-status` transitions instead of blocking startup on a
+            // one-shot health wait.
+            start_health_poller(app.handle());
 
             Ok(())
         })
@@ -147,7 +207,22 @@ fn main() {
             git_status,
             git_log,
             git_diff,
+            git_diff_hunks,
+            git_list_branches,
+            git_create_branch,
+            git_checkout_branch,
+            git_delete_branch,
+            git_clone,
+            git_add_remote,
+            git_fetch,
+            git_push,
+            git_pull,
+            git_export_patch,
+            git_export_patch_range,
             restore_file_version,
+            start_watching,
+            stop_watching,
+            render_markdown,
             check_backend_health,
             select_directory,
             get_home_dir
@@ -0,0 +1,139 @@
+use comrak::nodes::{NodeHtmlBlock, NodeValue};
+use comrak::{format_html, parse_document, Arena, ComrakOptions};
+use moka::sync::Cache;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::time::Duration;
+use syntect::html::{ClassStyle, ClassedHTMLGenerator};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+const TTL_SECS: u64 = 30;
+const MAX_ENTRIES: u64 = 128;
+
+/// Caches rendered HTML keyed by a hash of the chapter's Markdown source, so
+/// repeated preview refreshes of an unchanged chapter skip re-rendering. The
+/// syntax set is loaded once and shared, since rebuilding it per render would
+/// reload every default syntax definition on each content edit.
+pub struct RenderCache {
+    html: Cache<u64, String>,
+    syntax_set: SyntaxSet,
+}
+
+impl Default for RenderCache {
+    fn default() -> Self {
+        RenderCache {
+            html: Cache::builder()
+                .time_to_live(Duration::from_secs(TTL_SECS))
+                .max_capacity(MAX_ENTRIES)
+                .build(),
+            syntax_set: SyntaxSet::load_defaults_newlines(),
+        }
+    }
+}
+
+fn comrak_options() -> ComrakOptions {
+    let mut options = ComrakOptions::default();
+    options.extension.table = true;
+    options.extension.strikethrough = true;
+    options.extension.footnotes = true;
+    options.extension.tasklist = true;
+    // The fenced code blocks are rewritten into raw HTML blocks holding the
+    // syntect-highlighted markup, so raw HTML must be emitted verbatim rather
+    // than replaced with `<!-- raw HTML omitted -->`.
+    options.render.unsafe_ = true;
+    options
+}
+
+fn hash_content(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Highlight a fenced code block into class-based HTML, falling back to a plain
+/// escaped `<pre>` when the language is unknown or absent.
+fn highlight_code(literal: &str, info: &str, syntax_set: &SyntaxSet) -> String {
+    let token = info.split_whitespace().next().unwrap_or("");
+
+    let syntax = if token.is_empty() {
+        None
+    } else {
+        syntax_set.find_syntax_by_token(token)
+    };
+
+    match syntax {
+        Some(syntax) => {
+            let mut generator =
+                ClassedHTMLGenerator::new_with_class_style(syntax, syntax_set, ClassStyle::Spaced);
+            for line in LinesWithEndings::from(literal) {
+                // If a line fails to parse, degrade to the plain fallback.
+                if generator.parse_html_for_line_which_includes_newline(line).is_err() {
+                    return plain_code(literal);
+                }
+            }
+            format!(
+                "<pre class=\"code\"><code class=\"language-{}\">{}</code></pre>\n",
+                token,
+                generator.finalize()
+            )
+        }
+        None => plain_code(literal),
+    }
+}
+
+fn plain_code(literal: &str) -> String {
+    format!("<pre><code>{}</code></pre>\n", escape_html(literal))
+}
+
+fn escape_html(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn render(content: &str, syntax_set: &SyntaxSet) -> String {
+    let options = comrak_options();
+    let arena = Arena::new();
+    let root = parse_document(&arena, content, &options);
+
+    // Replace each fenced code block with a pre-highlighted raw HTML block.
+    for node in root.descendants() {
+        let mut data = node.data.borrow_mut();
+        if let NodeValue::CodeBlock(ref code_block) = data.value {
+            let html = highlight_code(&code_block.literal, &code_block.info, syntax_set);
+            data.value = NodeValue::HtmlBlock(NodeHtmlBlock {
+                literal: html,
+                block_type: 0,
+            });
+        }
+    }
+
+    let mut output = Vec::new();
+    // Formatting into a Vec never fails for an in-memory writer.
+    let _ = format_html(root, &options, &mut output);
+    String::from_utf8_lossy(&output).into_owned()
+}
+
+#[tauri::command]
+pub fn render_markdown(cache: tauri::State<'_, RenderCache>, path_or_content: String) -> Result<String, String> {
+    // Treat the argument as a file path when it points at an existing file,
+    // otherwise render it directly as Markdown source.
+    let content = if Path::new(&path_or_content).is_file() {
+        std::fs::read_to_string(&path_or_content)
+            .map_err(|e| format!("Failed to read file: {}", e))?
+    } else {
+        path_or_content
+    };
+
+    let key = hash_content(&content);
+    if let Some(cached) = cache.html.get(&key) {
+        return Ok(cached);
+    }
+
+    let html = render(&content, &cache.syntax_set);
+    cache.html.insert(key, html.clone());
+    Ok(html)
+}